@@ -0,0 +1,139 @@
+use std::env;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 24;
+pub const SALT_LEN: usize = 16;
+pub const FORMAT_VERSION: u8 = 1;
+
+const DEFAULT_M_COST: u32 = 19 * 1024;
+const DEFAULT_T_COST: u32 = 2;
+const DEFAULT_P_COST: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VaultHeader {
+    pub version: u8,
+    #[serde(with = "base64_bytes")]
+    pub salt: Vec<u8>,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    #[serde(with = "base64_bytes")]
+    pub nonce: Vec<u8>,
+}
+
+/// A payload encrypted under a passphrase: the header is authenticated as
+/// associated data so tampering with the KDF parameters is also detected.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncryptedPayload {
+    #[serde(flatten)]
+    pub header: VaultHeader,
+    #[serde(with = "base64_bytes")]
+    pub ciphertext: Vec<u8>,
+}
+
+fn derive_key(passphrase: &str, header: &VaultHeader) -> anyhow::Result<[u8; KEY_LEN]> {
+    let params = Params::new(header.m_cost, header.t_cost, header.p_cost, Some(KEY_LEN))
+        .map_err(|e| anyhow::anyhow!("invalid Argon2id parameters: {e}"))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let mut key = [0u8; KEY_LEN];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), &header.salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("failed to derive key: {e}"))?;
+    Ok(key)
+}
+
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> anyhow::Result<EncryptedPayload> {
+    let mut rng = rand::thread_rng();
+    let mut salt = vec![0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = vec![0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let header = VaultHeader {
+        version: FORMAT_VERSION,
+        salt,
+        m_cost: DEFAULT_M_COST,
+        t_cost: DEFAULT_T_COST,
+        p_cost: DEFAULT_P_COST,
+        nonce,
+    };
+    let key = derive_key(passphrase, &header)?;
+    let cipher = XChaCha20Poly1305::new(key.as_slice().into());
+    let aad = serde_json::to_vec(&header)?;
+    let ciphertext = cipher
+        .encrypt(
+            header.nonce.as_slice().into(),
+            Payload { msg: plaintext, aad: &aad },
+        )
+        .map_err(|e| anyhow::anyhow!("failed to encrypt vault: {e}"))?;
+    Ok(EncryptedPayload { header, ciphertext })
+}
+
+/// A wrong passphrase surfaces as a clear error rather than garbage bytes,
+/// since the AEAD tag won't verify.
+pub fn decrypt(payload: &EncryptedPayload, passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    let key = derive_key(passphrase, &payload.header)?;
+    let cipher = XChaCha20Poly1305::new(key.as_slice().into());
+    let aad = serde_json::to_vec(&payload.header)?;
+    cipher
+        .decrypt(
+            payload.header.nonce.as_slice().into(),
+            Payload { msg: &payload.ciphertext, aad: &aad },
+        )
+        .map_err(|_| anyhow::anyhow!("bad passphrase: failed to decrypt vault"))
+}
+
+pub fn prompt_passphrase(prompt: &str, env_var: &str) -> anyhow::Result<String> {
+    if let Ok(passphrase) = env::var(env_var) {
+        return Ok(passphrase);
+    }
+    rpassword::prompt_password(prompt).map_err(|e| anyhow::anyhow!("failed to read passphrase: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let payload = encrypt(b"hunter2", "correct horse battery staple").unwrap();
+        let plaintext = decrypt(&payload, "correct horse battery staple").unwrap();
+        assert_eq!(plaintext, b"hunter2");
+    }
+
+    #[test]
+    fn decrypt_fails_with_wrong_passphrase() {
+        let payload = encrypt(b"hunter2", "correct horse battery staple").unwrap();
+        assert!(decrypt(&payload, "wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn decrypt_fails_on_tampered_ciphertext() {
+        let mut payload = encrypt(b"hunter2", "correct horse battery staple").unwrap();
+        payload.ciphertext[0] ^= 0xff;
+        assert!(decrypt(&payload, "correct horse battery staple").is_err());
+    }
+}
+
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        STANDARD.encode(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+const HOOKS_FILE: &'static str = "./hooks.json";
+
+/// External commands run at defined points in `pw`'s lifecycle. The event
+/// name and affected key are passed along; the plaintext password never is.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct HookConfig {
+    pub pre_load: Option<String>,
+    pub post_save: Option<String>,
+    pub on_set: Option<String>,
+    pub on_remove: Option<String>,
+    pub on_show: Option<String>,
+    pub on_list: Option<String>,
+}
+
+impl HookConfig {
+    pub fn load() -> anyhow::Result<Self> {
+        if !fs::metadata(HOOKS_FILE).is_ok() {
+            return Ok(Self::default());
+        }
+        let file = fs::File::open(HOOKS_FILE)?;
+        serde_json::from_reader(file).map_err(|e| io::Error::from(e).into())
+    }
+
+    fn run(command: &str, event: &str, key: Option<&str>) -> io::Result<bool> {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command).env("PW_EVENT", event);
+        if let Some(key) = key {
+            cmd.env("PW_KEY", key);
+        }
+        Ok(cmd.status()?.success())
+    }
+
+    /// Aborts the calling operation if the hook exits non-zero.
+    pub fn run_pre_load(&self) -> anyhow::Result<()> {
+        let Some(command) = &self.pre_load else { return Ok(()) };
+        if !Self::run(command, "load", None)? {
+            anyhow::bail!("pre_load hook \"{command}\" exited non-zero; aborting");
+        }
+        Ok(())
+    }
+
+    /// Failures are logged, not fatal: the write already succeeded.
+    pub fn run_post_save(&self) {
+        self.notify(&self.post_save, "save", None);
+    }
+
+    pub fn notify_set(&self, key: &str) {
+        self.notify(&self.on_set, "set", Some(key));
+    }
+
+    pub fn notify_remove(&self, key: &str) {
+        self.notify(&self.on_remove, "remove", Some(key));
+    }
+
+    pub fn notify_show(&self, key: &str) {
+        self.notify(&self.on_show, "show", Some(key));
+    }
+
+    pub fn notify_list(&self) {
+        self.notify(&self.on_list, "list", None);
+    }
+
+    fn notify(&self, hook: &Option<String>, event: &str, key: Option<&str>) {
+        let Some(command) = hook else { return };
+        match Self::run(command, event, key) {
+            Ok(true) => {}
+            Ok(false) => eprintln!("warning: {event} hook \"{command}\" exited non-zero"),
+            Err(e) => eprintln!("warning: {event} hook \"{command}\" failed to run: {e}"),
+        }
+    }
+}
@@ -1,25 +1,44 @@
-use std::{collections::HashSet, fs, io::{self}};
+use std::{collections::HashSet, fs, io::{self}, thread, time::Duration};
 
 use anyhow::anyhow;
 use arboard::Clipboard;
 use clap::{Parser, Subcommand};
-use keyring::Entry;
 use serde::{Deserialize, Serialize};
 
+use hooks::HookConfig;
+use store::{Backend, SecretStore};
+
+mod crypto;
+mod hooks;
+mod migrate;
+mod store;
+mod vault;
+
 const FILE_DIR: &'static str = "./keys.json";
 const SERVICE: &'static str = "pw-cli";
+/// Seconds the clipboard is left untouched after `--copy` when `--clear` isn't given.
+const DEFAULT_CLEAR_SECS: u64 = 20;
 
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
     key: Option<String>,
+    /// Also copy the password to the clipboard (it is still printed unless --quiet is given).
     #[arg(short, long, global(true))]
     copy: bool,
+    /// Suppress output, including the printed password and error messages.
     #[arg(short, long, global(true))]
     quiet: bool,
+    /// Only operate on the entry matching this tag.
     #[arg(short, long, global(true))]
-    tag: Option<String>
+    tag: Option<String>,
+    /// Seconds to wait before clearing the clipboard after --copy (0 to disable). Defaults to 20.
+    #[arg(long, global(true), value_name = "SECONDS")]
+    clear: Option<u64>,
+    /// Which secret store to read and write passwords from.
+    #[arg(long, global(true), value_enum, default_value = "keyring")]
+    backend: Backend
 }
 
 #[derive(Subcommand)]
@@ -36,23 +55,38 @@ enum Commands {
         #[arg(short, long, global(true))]
         tag: Option<String> 
     },
-    List { 
+    List {
         #[arg(short, long, global(true))]
         tag: Option<String>,
         #[arg(long("no-tag"), conflicts_with("tag"))]
         no_tag: bool
-    } 
+    },
+    /// Migrate keys.json to the current storage format, backing up the old file first.
+    Upgrade,
+    /// Export every key and its secret to an encrypted, portable vault file.
+    Export { path: String },
+    /// Import an encrypted vault file produced by `pw export`.
+    Import {
+        path: String,
+        #[arg(long)]
+        overwrite: bool
+    }
 }
 
+/// Files with no `version` key read as version 0 via `#[serde(default)]`.
+pub(crate) const CURRENT_STORAGE_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Debug, Default)]
-struct KeyStorage {
-    keys: HashSet<(String, Option<String>)>
+pub(crate) struct KeyStorage {
+    #[serde(default)]
+    pub(crate) version: u32,
+    pub(crate) keys: HashSet<(String, Option<String>)>
 }
 
 impl KeyStorage {
     fn load() -> io::Result<Self> {
         if !fs::metadata(FILE_DIR).is_ok() {
-            return Ok(Self::default())
+            return Ok(Self { version: CURRENT_STORAGE_VERSION, ..Self::default() })
         }
         let file = fs::File::open(FILE_DIR)?;
         serde_json::from_reader(file)
@@ -67,28 +101,27 @@ impl KeyStorage {
 }
 
 impl Cli {
-    fn entry(key: &str) -> keyring::Result<Entry> {
-        Entry::new_with_target(key, SERVICE, &whoami::username())
-    }
-
-    fn handle_set_command(key: String, password: String, quiet: bool, tag: Option<String>) -> anyhow::Result<()> {
-        let mut storage = KeyStorage::load()?;                
+    fn handle_set_command(store: &mut dyn SecretStore, hooks: &HookConfig, key: String, password: String, quiet: bool, tag: Option<String>) -> anyhow::Result<()> {
+        hooks.run_pre_load()?;
+        let mut storage = KeyStorage::load()?;
         let entry_name = match &tag {
             Some(tag) => format!("{key}:{tag}"),
             None => key.to_string(),
         };
-        let entry = Self::entry(&entry_name)?;
-        entry.set_password(&password)?;                        
+        store.set(&entry_name, &password)?;
         if storage.keys.insert((key, tag)) {
             storage.save()?;
+            hooks.run_post_save();
         }
+        hooks.notify_set(&entry_name);
         if !quiet {
             println!("Password for \"{entry_name}\" set successfully.");
         }
         Ok(())
     }
 
-    fn handle_password(key: String, quiet: bool, copy: bool, tag: Option<String>) -> anyhow::Result<()> {        
+    fn handle_password(store: &dyn SecretStore, hooks: &HookConfig, key: String, quiet: bool, copy: bool, tag: Option<String>, clear: Option<u64>) -> anyhow::Result<()> {
+        hooks.run_pre_load()?;
         let matched_keys = KeyStorage::load()?.keys
             .iter()
             .filter_map(|(k, t)| (k == &key).then(|| {                
@@ -113,25 +146,40 @@ impl Cli {
         let Some((_, entry_name)) = matched_keys.first() else {
             panic!("Key \"{key}\" was found initially, but returned None.");
         };
-        let entry = Self::entry(&entry_name)?;
-        let pw = entry.get_password()?;                
+        let pw = store.get(&entry_name)?;
+        hooks.notify_show(&entry_name);
         if !quiet {
             println!("The password for \"{entry_name}\" is \"{pw}\".");
         }
         if copy {
             let mut clipboard = Clipboard::new()
                 .map_err(|e| anyhow!("Failed to initialize clipboard: {e}"))?;
-            clipboard.set_text(pw)
+            clipboard.set_text(pw.clone())
                 .map_err(|e| anyhow!("Failed to set clipboard content: {e}"))?;
             if !quiet {
                 println!("Copied to the clipboard!");
             }
+
+            let clear_after = clear.unwrap_or(DEFAULT_CLEAR_SECS);
+            if clear_after > 0 {
+                if !quiet {
+                    println!("The clipboard will be cleared in {clear_after}s.");
+                }
+                // arboard needs this process alive to keep serving the X11/Wayland
+                // selection, so the clear timer has to block here rather than detach.
+                thread::sleep(Duration::from_secs(clear_after));
+                if clipboard.get_text().map(|current| current == pw).unwrap_or(false) {
+                    let _ = clipboard.set_text(String::new());
+                }
+            }
         }
         Ok(())
     }
 
-    fn handle_list_command(quiet: bool, tag: Option<String>, no_tag: bool) -> anyhow::Result<()> {
-        if !quiet {            
+    fn handle_list_command(hooks: &HookConfig, quiet: bool, tag: Option<String>, no_tag: bool) -> anyhow::Result<()> {
+        hooks.run_pre_load()?;
+        hooks.notify_list();
+        if !quiet {
             let storage = KeyStorage::load()
                 .unwrap_or_default();
             if !storage.keys.is_empty() {
@@ -163,8 +211,9 @@ impl Cli {
         Ok(())
     }
 
-    fn handle_remove_command(key: String, quiet: bool, tag: Option<String>) -> anyhow::Result<()> {        
-        let mut storage = KeyStorage::load()?;        
+    fn handle_remove_command(store: &mut dyn SecretStore, hooks: &HookConfig, key: String, quiet: bool, tag: Option<String>) -> anyhow::Result<()> {
+        hooks.run_pre_load()?;
+        let mut storage = KeyStorage::load()?;
         let matched_keys = storage.keys
             .iter()
             .filter_map(|(k, t)| (k == &key).then(|| {                
@@ -191,9 +240,10 @@ impl Cli {
                 if !storage.keys.remove(&storage_key) {
                     panic!("Key found in storage previously failed on removal.");
                 }
-                let entry = Self::entry(&key_str)?;
-                entry.delete_credential()?;
+                store.delete(&key_str)?;
                 storage.save()?;
+                hooks.run_post_save();
+                hooks.notify_remove(&key_str);
                 format!("\"{key_str}\" removed successfully.")
             },
             None => format!("\"{key}\" not found."),                     
@@ -203,25 +253,56 @@ impl Cli {
         }
         Ok(())
     }
+
+    fn handle_upgrade_command(hooks: &HookConfig, quiet: bool) -> anyhow::Result<()> {
+        migrate::upgrade(hooks, quiet)
+    }
+
+    fn handle_export_command(store: &dyn SecretStore, hooks: &HookConfig, path: String, quiet: bool) -> anyhow::Result<()> {
+        vault::export(store, hooks, &path, quiet)
+    }
+
+    fn handle_import_command(store: &mut dyn SecretStore, hooks: &HookConfig, path: String, overwrite: bool, quiet: bool) -> anyhow::Result<()> {
+        vault::import(store, hooks, &path, overwrite, quiet)
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    let hooks = match HookConfig::load() {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            if !cli.quiet {
+                eprintln!("An error has occured! Error: {e}");
+            }
+            return;
+        }
+    };
+    // Only built for the commands that actually touch secret storage, so
+    // `list`/`upgrade`/a bare `pw` never trigger the file backend's
+    // passphrase prompt or create a vault.
     let err = match cli.command {
-        Some(Commands::Set { key, password, tag }) 
-            => Cli::handle_set_command(key, password, cli.quiet, tag),
-        Some(Commands::List { tag, no_tag }) 
-            => Cli::handle_list_command(cli.quiet, tag, no_tag),
-        Some(Commands::Remove { key, tag }) 
-            => Cli::handle_remove_command(key, cli.quiet, tag),
+        Some(Commands::Set { key, password, tag }) => store::build_store(cli.backend)
+            .and_then(|mut store| Cli::handle_set_command(store.as_mut(), &hooks, key, password, cli.quiet, tag)),
+        Some(Commands::List { tag, no_tag })
+            => Cli::handle_list_command(&hooks, cli.quiet, tag, no_tag),
+        Some(Commands::Remove { key, tag }) => store::build_store(cli.backend)
+            .and_then(|mut store| Cli::handle_remove_command(store.as_mut(), &hooks, key, cli.quiet, tag)),
+        Some(Commands::Upgrade)
+            => Cli::handle_upgrade_command(&hooks, cli.quiet),
+        Some(Commands::Export { path }) => store::build_store(cli.backend)
+            .and_then(|store| Cli::handle_export_command(store.as_ref(), &hooks, path, cli.quiet)),
+        Some(Commands::Import { path, overwrite }) => store::build_store(cli.backend)
+            .and_then(|mut store| Cli::handle_import_command(store.as_mut(), &hooks, path, overwrite, cli.quiet)),
         None => match cli.key {
-            Some(key) => Cli::handle_password(key, cli.quiet, cli.copy, cli.tag),
+            Some(key) => store::build_store(cli.backend)
+                .and_then(|store| Cli::handle_password(store.as_ref(), &hooks, key, cli.quiet, cli.copy, cli.tag, cli.clear)),
             None => return,
         }
     };
-    if !cli.quiet { 
+    if !cli.quiet {
         if let Err(e) = err {
            eprintln!("An error has occured! Error: {e}");
         }
-    }    
+    }
 }
@@ -0,0 +1,48 @@
+use std::fs;
+
+use crate::hooks::HookConfig;
+use crate::{KeyStorage, CURRENT_STORAGE_VERSION, FILE_DIR};
+
+type Migration = fn(KeyStorage) -> KeyStorage;
+
+/// Ordered chain of migrations, each keyed by the version it produces.
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v0_to_v1)];
+
+fn migrate_v0_to_v1(mut storage: KeyStorage) -> KeyStorage {
+    storage.version = 1;
+    storage
+}
+
+pub fn upgrade(hooks: &HookConfig, quiet: bool) -> anyhow::Result<()> {
+    hooks.run_pre_load()?;
+    let mut storage = KeyStorage::load()?;
+    if storage.version == CURRENT_STORAGE_VERSION {
+        if !quiet {
+            println!("{FILE_DIR} is already at version {CURRENT_STORAGE_VERSION}.");
+        }
+        return Ok(());
+    }
+    if storage.version > CURRENT_STORAGE_VERSION {
+        anyhow::bail!(
+            "{FILE_DIR} is version {}, but this build of pw only supports up to version {CURRENT_STORAGE_VERSION}; upgrade pw first",
+            storage.version
+        );
+    }
+
+    let old_version = storage.version;
+    let backup_path = format!("{FILE_DIR}.v{old_version}.bak");
+    fs::copy(FILE_DIR, &backup_path)?;
+
+    for &(version, migrate) in MIGRATIONS {
+        if storage.version < version {
+            storage = migrate(storage);
+        }
+    }
+    storage.save()?;
+    hooks.run_post_save();
+
+    if !quiet {
+        println!("Upgraded {FILE_DIR} from version {old_version} to {CURRENT_STORAGE_VERSION} (backup saved to {backup_path}).");
+    }
+    Ok(())
+}
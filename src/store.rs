@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use clap::ValueEnum;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, EncryptedPayload};
+use crate::SERVICE;
+
+const VAULT_FILE: &'static str = "./vault.json";
+const PASSPHRASE_ENV: &'static str = "PW_VAULT_PASSPHRASE";
+
+/// Which `SecretStore` implementation a run of `pw` should use.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum Backend {
+    /// Store secrets in the OS keyring / secret service (the historical default).
+    #[default]
+    Keyring,
+    /// Store secrets in a single local file, for machines with no keyring daemon.
+    File,
+}
+
+// `keys.json` (via `KeyStorage`) is the sole source of truth for key names
+// and tags regardless of backend, so `SecretStore` only deals in values.
+pub trait SecretStore {
+    fn set(&mut self, name: &str, password: &str) -> anyhow::Result<()>;
+    fn get(&self, name: &str) -> anyhow::Result<String>;
+    fn delete(&mut self, name: &str) -> anyhow::Result<()>;
+}
+
+pub fn build_store(backend: Backend) -> anyhow::Result<Box<dyn SecretStore>> {
+    match backend {
+        Backend::Keyring => Ok(Box::new(KeyringStore)),
+        Backend::File => Ok(Box::new(FileStore::load()?)),
+    }
+}
+
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(name: &str) -> keyring::Result<Entry> {
+        Entry::new_with_target(name, SERVICE, &whoami::username())
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn set(&mut self, name: &str, password: &str) -> anyhow::Result<()> {
+        Self::entry(name)?.set_password(password)?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<String> {
+        Ok(Self::entry(name)?.get_password()?)
+    }
+
+    fn delete(&mut self, name: &str) -> anyhow::Result<()> {
+        Self::entry(name)?.delete_credential()?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct VaultContents {
+    secrets: HashMap<String, String>,
+}
+
+/// Secrets held in a single local file, encrypted under a master passphrase.
+pub struct FileStore {
+    contents: VaultContents,
+    passphrase: String,
+}
+
+impl FileStore {
+    fn load() -> anyhow::Result<Self> {
+        if !fs::metadata(VAULT_FILE).is_ok() {
+            let passphrase = crypto::prompt_passphrase("Create a passphrase for the new vault: ", PASSPHRASE_ENV)?;
+            return Ok(Self { contents: VaultContents::default(), passphrase });
+        }
+        let file = fs::File::open(VAULT_FILE)?;
+        let payload: EncryptedPayload = serde_json::from_reader(file).map_err(io::Error::from)?;
+        let passphrase = crypto::prompt_passphrase("Vault passphrase: ", PASSPHRASE_ENV)?;
+        let plaintext = crypto::decrypt(&payload, &passphrase)?;
+        let contents = serde_json::from_slice(&plaintext)?;
+        Ok(Self { contents, passphrase })
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let plaintext = serde_json::to_vec(&self.contents)?;
+        let payload = crypto::encrypt(&plaintext, &self.passphrase)?;
+        let file = fs::File::create(VAULT_FILE)?;
+        serde_json::to_writer_pretty(file, &payload)?;
+        Ok(())
+    }
+}
+
+impl SecretStore for FileStore {
+    fn set(&mut self, name: &str, password: &str) -> anyhow::Result<()> {
+        self.contents.secrets.insert(name.to_string(), password.to_string());
+        self.save()
+    }
+
+    fn get(&self, name: &str) -> anyhow::Result<String> {
+        self.contents
+            .secrets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no secret named \"{name}\" in {VAULT_FILE}"))
+    }
+
+    fn delete(&mut self, name: &str) -> anyhow::Result<()> {
+        self.contents
+            .secrets
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("no secret named \"{name}\" in {VAULT_FILE}"))?;
+        self.save()
+    }
+}
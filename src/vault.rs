@@ -0,0 +1,78 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, EncryptedPayload};
+use crate::hooks::HookConfig;
+use crate::store::SecretStore;
+use crate::KeyStorage;
+
+const EXPORT_PASSPHRASE_ENV: &'static str = "PW_EXPORT_PASSPHRASE";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct VaultEntry {
+    key: String,
+    tag: Option<String>,
+    secret: String,
+}
+
+pub fn export(store: &dyn SecretStore, hooks: &HookConfig, path: &str, quiet: bool) -> anyhow::Result<()> {
+    hooks.run_pre_load()?;
+    let storage = KeyStorage::load()?;
+    let mut entries = Vec::with_capacity(storage.keys.len());
+    for (key, tag) in storage.keys {
+        let entry_name = match &tag {
+            Some(tag) => format!("{key}:{tag}"),
+            None => key.clone(),
+        };
+        let secret = store.get(&entry_name)?;
+        entries.push(VaultEntry { key, tag, secret });
+    }
+
+    let passphrase = crypto::prompt_passphrase("Passphrase to encrypt the export with: ", EXPORT_PASSPHRASE_ENV)?;
+    let plaintext = serde_json::to_vec(&entries)?;
+    let payload = crypto::encrypt(&plaintext, &passphrase)?;
+    let file = fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, &payload)?;
+
+    if !quiet {
+        println!("Exported {} entries to \"{path}\".", entries.len());
+    }
+    Ok(())
+}
+
+/// Existing (key, tag) pairs are skipped unless `overwrite` is set.
+pub fn import(store: &mut dyn SecretStore, hooks: &HookConfig, path: &str, overwrite: bool, quiet: bool) -> anyhow::Result<()> {
+    hooks.run_pre_load()?;
+    let file = fs::File::open(path)?;
+    let payload: EncryptedPayload = serde_json::from_reader(file)?;
+    let passphrase = crypto::prompt_passphrase("Passphrase the export was encrypted with: ", EXPORT_PASSPHRASE_ENV)?;
+    let plaintext = crypto::decrypt(&payload, &passphrase)?;
+    let entries: Vec<VaultEntry> = serde_json::from_slice(&plaintext)?;
+
+    let mut storage = KeyStorage::load()?;
+    let mut imported = 0;
+    let mut skipped = 0;
+    for entry in entries {
+        let already_present = storage.keys.contains(&(entry.key.clone(), entry.tag.clone()));
+        if already_present && !overwrite {
+            skipped += 1;
+            continue;
+        }
+        let entry_name = match &entry.tag {
+            Some(tag) => format!("{}:{tag}", entry.key),
+            None => entry.key.clone(),
+        };
+        store.set(&entry_name, &entry.secret)?;
+        storage.keys.insert((entry.key, entry.tag));
+        hooks.notify_set(&entry_name);
+        imported += 1;
+    }
+    storage.save()?;
+    hooks.run_post_save();
+
+    if !quiet {
+        println!("Imported {imported} entries from \"{path}\" ({skipped} skipped; pass --overwrite to replace them).");
+    }
+    Ok(())
+}